@@ -0,0 +1,50 @@
+//! Command-line argument parsing.
+
+/// How diagnostics emitted by this program should be formatted.
+#[derive(Debug, Clone, Copy)]
+pub enum ErrorFormat {
+  /// The default: human-readable, colored terminal output.
+  Human,
+  /// One JSON object per diagnostic, written as line-delimited JSON.
+  Json,
+}
+
+/// The parsed command-line arguments.
+#[derive(Debug)]
+pub struct Args {
+  pub files: Vec<String>,
+  pub error_format: ErrorFormat,
+  /// Set by `--explain <CODE>`. When present, `main` should print the explanation for this code
+  /// and exit without processing `files`.
+  pub explain: Option<String>,
+}
+
+/// Parses this process's command-line arguments.
+pub fn get() -> Args {
+  let mut files = Vec::new();
+  let mut error_format = ErrorFormat::Human;
+  let mut explain = None;
+  let mut args = std::env::args().skip(1);
+  while let Some(arg) = args.next() {
+    match arg.strip_prefix("--error-format=") {
+      Some("human") => error_format = ErrorFormat::Human,
+      Some("json") => error_format = ErrorFormat::Json,
+      Some(other) => {
+        eprintln!("unknown --error-format: {}", other);
+        std::process::exit(1);
+      }
+      None if arg == "--explain" => {
+        explain = Some(args.next().unwrap_or_else(|| {
+          eprintln!("--explain requires a code argument");
+          std::process::exit(1);
+        }));
+      }
+      None => files.push(arg),
+    }
+  }
+  Args {
+    files,
+    error_format,
+    explain,
+  }
+}
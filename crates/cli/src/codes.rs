@@ -0,0 +1,286 @@
+//! Stable, greppable codes for every diagnostic Millet can emit, plus the longer explanations
+//! shown by `millet --explain <code>`.
+//!
+//! Codes are assigned in exactly one place, the `codes!` invocation below, so two error variants
+//! can never collide on the same code and a code's meaning can never silently change.
+
+/// One entry in the explanation catalog.
+pub struct Explanation {
+  pub code: &'static str,
+  pub text: &'static str,
+}
+
+macro_rules! codes {
+  ($($const_name:ident => ($code:literal, $text:expr),)*) => {
+    $(pub const $const_name: &str = $code;)*
+
+    /// Every known code and its explanation, in declaration order.
+    pub const ALL: &[Explanation] = &[
+      $(Explanation { code: $code, text: $text },)*
+    ];
+  };
+}
+
+codes! {
+  E_LEX_UNMATCHED_CLOSE_COMMENT => ("E1001", "\
+A `*)` was found with no matching `(*` before it.
+
+    1 + 2 *)
+
+Remove the stray `*)`, or add the `(*` it was meant to close."),
+
+  E_LEX_UNMATCHED_OPEN_COMMENT => ("E1002", "\
+A `(*` was opened but never closed before the end of the file.
+
+    (* a comment that never ends
+    val x = 1
+
+Add the matching `*)`. Comments nest, so each `(*` needs its own `*)`."),
+
+  E_LEX_INCOMPLETE_TYPE_VAR => ("E1003", "\
+A type variable started with `'` but was not followed by a valid name.
+
+    val x : ' = 1
+
+Follow the `'` (or `''` for an equality type variable) with at least one
+letter, digit, or `_`, e.g. `'a`."),
+
+  E_LEX_UNKNOWN_BYTE => ("E1004", "\
+A byte appeared that is not part of any token in Standard ML.
+
+    val x = 1 ` 2
+
+Remove the offending character, or check for a stray non-ASCII byte pasted
+in from elsewhere."),
+
+  E_LEX_INVALID_INT_CONSTANT => ("E1005", "\
+A sequence of digits was too large to fit in Millet's integer representation.
+
+    val x = 99999999999999999999
+
+Use a smaller literal, or `~` plus a smaller literal for a large negative
+value."),
+
+  E_LEX_INVALID_REAL_CONSTANT => ("E1006", "\
+A real number literal could not be parsed.
+
+    val x = 1.
+
+Standard ML requires at least one digit after the decimal point, e.g.
+`1.0`."),
+
+  E_LEX_NEGATIVE_WORD_CONSTANT => ("E1007", "\
+A word constant (`0wN`) was written with a leading `~`.
+
+    val x = ~0w1
+
+Word constants are unsigned; drop the `~`."),
+
+  E_LEX_INCOMPLETE_NUM_CONSTANT => ("E1008", "\
+A numeric literal ended where more digits were expected, e.g. after `0w` or
+an exponent marker `E`/`e`.
+
+    val x = 0w
+    val y = 1E
+
+Add the missing digits, or remove the trailing marker."),
+
+  E_LEX_UNCLOSED_STRING_CONSTANT => ("E1009", "\
+A string literal's opening `\"` was never closed before the end of the line
+or file.
+
+    val x = \"hello
+
+Add the closing `\"`."),
+
+  E_LEX_INVALID_STRING_CONSTANT => ("E1010", "\
+A string literal contained an invalid escape sequence or character.
+
+    val x = \"\\q\"
+
+Use one of the escapes Millet's lexer accepts: `\\n`, `\\t`, `\\\\`, or `\\\"`.
+Any other escape, including `\\uXXXX`, is reported as invalid."),
+
+  E_LEX_INVALID_CHAR_CONSTANT => ("E1011", "\
+A character literal (`#\"...\"`) did not contain exactly one character.
+
+    val x = #\"ab\"
+
+Character literals must denote exactly one character, e.g. `#\"a\"`."),
+
+  E_PARSE_EXPECTED_BUT_FOUND => ("E2001", "\
+The parser expected one kind of token next but found another.
+
+    val = 1
+
+Here a variable name was expected after `val` but `=` was found instead.
+Check the surrounding syntax against the grammar for the construct being
+written."),
+
+  E_PARSE_INFIX_WITHOUT_OP => ("E2002", "\
+An identifier declared `infix` was used as an ordinary (non-infix, prefix)
+identifier without the `op` keyword.
+
+    infix ++
+    val f = ++
+
+Write `op ++` to use an infix identifier in a non-infix position."),
+
+  E_PARSE_NOT_INFIX => ("E2003", "\
+An identifier was used as an infix operator, but it was never declared
+`infix` or `infixr`.
+
+    val x = 1 foo 2
+
+Add an `infix` (or `infixr`) declaration for the identifier, or call it as
+an ordinary prefix function: `foo 1 2`."),
+
+  E_PARSE_REAL_PAT => ("E2004", "\
+A real number constant was used directly in a pattern, which Standard ML
+forbids because real equality is unreliable.
+
+    val 1.0 = x
+
+Match on a variable and compare with `Real.==` (or a tolerance check)
+instead."),
+
+  E_PARSE_NEGATIVE_FIXITY => ("E2005", "\
+An `infix`/`infixr` declaration gave a negative precedence.
+
+    infix ~1 ++
+
+Fixity must be a non-negative integer, usually between 0 and 9."),
+
+  E_STATICS_UNDEFINED => ("E3001", "\
+An identifier was used that is not bound in scope.
+
+    val x = y
+
+Check for a typo, a missing `open`, or a missing `structure`/`signature`
+that should have brought the identifier into scope."),
+
+  E_STATICS_REDEFINED => ("E3002", "\
+An identifier was bound twice in a context where Standard ML forbids
+rebinding it, e.g. twice in the same pattern.
+
+    fun f (x, x) = x
+
+Give the two bindings different names."),
+
+  E_STATICS_DUPLICATE_LABEL => ("E3003", "\
+A record expression or pattern used the same label more than once.
+
+    val r = { a = 1, a = 2 }
+
+Remove or rename the duplicate label."),
+
+  E_STATICS_CIRCULARITY => ("E3004", "\
+Unifying two types would require a type variable to occur inside its own
+solution, which would produce an infinite type.
+
+    fun f x = x x
+
+Here `x` would need to be a function from itself to something, which has
+no finite type. This usually indicates a genuine type error rather than a
+missing annotation."),
+
+  E_STATICS_HEAD_MISMATCH => ("E3005", "\
+Two types were required to be equal, but their head type constructors
+differ (e.g. `int` vs `bool`).
+
+    val x : int = true
+
+Change the expression, or the annotation, so both sides agree."),
+
+  E_STATICS_MISSING_LABEL => ("E3006", "\
+A record type was expected to have a label that it does not have.
+
+    val { a, b } = { a = 1 }
+
+Add the missing label to the record, or remove it from the pattern."),
+
+  E_STATICS_VAL_AS_PAT => ("E3007", "\
+A value binding (a constructor with no arguments, like `nil` or a
+user-defined nullary constructor) was used where a variable pattern was
+expected and would shadow it.
+
+    fun f nil = 1 | f _ = 2
+
+This is usually intentional pattern matching and not actually an error in
+most contexts; this diagnostic flags the specific cases Standard ML
+forbids."),
+
+  E_STATICS_WRONG_NUM_TY_ARGS => ("E3008", "\
+A type constructor was applied to the wrong number of type arguments.
+
+    type 'a t = 'a list
+    val x : (int, bool) t = 1
+
+`t` takes exactly one type argument; supply exactly one."),
+
+  E_STATICS_NON_VAR_IN_AS => ("E3009", "\
+The pattern to the left of `as` was not a simple variable.
+
+    fun f (1 as p) = p
+
+Standard ML's `pat as pat` layered pattern requires a variable (optionally
+with a type annotation) to the left of `as`. Bind the whole pattern to a
+variable first, then match on it separately if needed."),
+
+  E_STATICS_FORBIDDEN_BINDING => ("E3010", "\
+An identifier that Standard ML reserves (like `true`, `false`, `nil`,
+`::`, or `ref`) was used as a new binding.
+
+    val true = 1
+
+Choose a different name; these identifiers have fixed meanings."),
+
+  E_STATICS_NO_SUITABLE_OVERLOAD => ("E3011", "\
+None of the candidate types for an overloaded operator (like `+` or `abs`)
+matched how it was used.
+
+    val x = 1 + \"a\"
+
+Overloaded operators only work across the numeric types they are defined
+for; check that both operands share a suitable numeric type."),
+
+  E_STATICS_TODO => ("E3012", "\
+This language construct is recognized by the parser but is not yet
+implemented by Millet's static analysis.
+
+There is no fix on the user's end; this indicates a gap in Millet itself."),
+}
+
+/// Looks up the explanation text for `code`, if it names a known diagnostic.
+pub fn explain(code: &str) -> Option<&'static str> {
+  ALL.iter().find(|e| e.code == code).map(|e| e.text)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::collections::HashSet;
+
+  #[test]
+  fn codes_never_collide() {
+    let codes: HashSet<_> = ALL.iter().map(|e| e.code).collect();
+    assert_eq!(codes.len(), ALL.len());
+  }
+
+  #[test]
+  fn every_code_has_nonempty_explanation_text() {
+    for e in ALL {
+      assert!(!e.text.is_empty(), "{} has no explanation text", e.code);
+    }
+  }
+
+  #[test]
+  fn explain_finds_a_known_code() {
+    assert_eq!(explain(E_LEX_UNMATCHED_CLOSE_COMMENT), Some(ALL[0].text));
+  }
+
+  #[test]
+  fn explain_rejects_an_unknown_code() {
+    assert_eq!(explain("E9999"), None);
+  }
+}
@@ -1,110 +1,345 @@
 //! Conversion from library error types to codespan Diagnostics.
 
-use crate::source::SourceId;
-use codespan_reporting::diagnostic::Label;
+use crate::codes;
+use crate::source::{SourceId, SourceMap};
+use codespan_reporting::diagnostic::{Label, Severity};
+use codespan_reporting::files::Files;
 use millet_core::ast::Label as AstLabel;
 use millet_core::intern::StrStore;
 use millet_core::lex::LexError;
-use millet_core::loc::Located;
+use millet_core::loc::{Loc, Located};
 use millet_core::parse::ParseError;
 use millet_core::statics::{StaticsError, Ty};
+use std::io::{self, Write as _};
 
 pub type Diagnostic = codespan_reporting::diagnostic::Diagnostic<SourceId>;
 
+/// Writes `diag` to `writer` as a single line of JSON, resolving each label's byte-offset span to
+/// a line/column position via `source_map`. This is the machine-readable counterpart to
+/// `codespan_reporting::term::emit`. Includes `diag.code` (the stable, greppable identifier
+/// `--explain` looks up) and each span's own `message`, so neither is lost on tooling that only
+/// consumes this format.
+pub fn emit_json(
+  writer: &mut impl io::Write,
+  source_map: &SourceMap,
+  diag: &Diagnostic,
+) -> io::Result<()> {
+  write!(writer, "{{\"severity\":\"{}\"", severity_str(diag.severity))?;
+  match &diag.code {
+    Some(code) => write!(writer, ",\"code\":{}", json_str(code))?,
+    None => write!(writer, ",\"code\":null")?,
+  }
+  write!(writer, ",\"message\":{}", json_str(&diag.message))?;
+  write!(writer, ",\"spans\":[")?;
+  for (idx, label) in diag.labels.iter().enumerate() {
+    if idx != 0 {
+      write!(writer, ",")?;
+    }
+    let file = source_map
+      .name(label.file_id)
+      .expect("label references a file not in the source map");
+    let (start_line, start_col) = resolve(source_map, label.file_id, label.range.start);
+    let (end_line, end_col) = resolve(source_map, label.file_id, label.range.end);
+    write!(
+      writer,
+      concat!(
+        "{{\"file\":{},\"start_byte\":{},\"end_byte\":{},",
+        "\"start_line\":{},\"start_col\":{},\"end_line\":{},\"end_col\":{},",
+        "\"message\":{}}}"
+      ),
+      json_str(&file.to_string()),
+      label.range.start,
+      label.range.end,
+      start_line + 1,
+      start_col + 1,
+      end_line + 1,
+      end_col + 1,
+      json_str(&label.message),
+    )?;
+  }
+  writeln!(writer, "]}}")
+}
+
+/// Resolves a byte offset into a `(line, column)` pair, both 0-indexed.
+fn resolve(source_map: &SourceMap, id: SourceId, byte_idx: usize) -> (usize, usize) {
+  let line_idx = source_map
+    .line_index(id, byte_idx)
+    .expect("byte index out of range for file");
+  let line_range = source_map
+    .line_range(id, line_idx)
+    .expect("line index returned by line_index must be valid");
+  (line_idx, byte_idx - line_range.start)
+}
+
+fn severity_str(sev: Severity) -> &'static str {
+  match sev {
+    Severity::Bug => "bug",
+    Severity::Error => "error",
+    Severity::Warning => "warning",
+    Severity::Note => "note",
+    Severity::Help => "help",
+  }
+}
+
+/// Renders `s` as a JSON string literal, escaping the handful of characters JSON requires.
+fn json_str(s: &str) -> String {
+  let mut buf = String::with_capacity(s.len() + 2);
+  buf.push('"');
+  for c in s.chars() {
+    match c {
+      '"' => buf.push_str("\\\""),
+      '\\' => buf.push_str("\\\\"),
+      '\n' => buf.push_str("\\n"),
+      '\r' => buf.push_str("\\r"),
+      '\t' => buf.push_str("\\t"),
+      c if (c as u32) < 0x20 => buf.push_str(&format!("\\u{:04x}", c as u32)),
+      c => buf.push(c),
+    }
+  }
+  buf.push('"');
+  buf
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn json_str_passes_through_plain_text() {
+    assert_eq!(json_str("hello"), "\"hello\"");
+  }
+
+  #[test]
+  fn json_str_escapes_quotes_and_backslashes() {
+    assert_eq!(json_str(r#"a"b\c"#), r#""a\"b\\c""#);
+  }
+
+  #[test]
+  fn json_str_escapes_whitespace_control_chars() {
+    assert_eq!(json_str("a\nb\tc\rd"), r#""a\nb\tc\rd""#);
+  }
+
+  #[test]
+  fn json_str_escapes_other_control_chars_as_unicode_sequences() {
+    assert_eq!(json_str("\u{1}"), r#""\u0001""#);
+  }
+}
+
 pub fn io(name: &str, err: std::io::Error) -> Diagnostic {
   Diagnostic::error().with_message(format!("{}: {}", name, err))
 }
 
-pub fn lex(id: SourceId, err: Located<LexError>) -> Diagnostic {
-  let msg = match err.val {
-    LexError::UnmatchedCloseComment => "unmatched close comment".to_owned(),
-    LexError::UnmatchedOpenComment => "unmatched open comment".to_owned(),
-    LexError::IncompleteTypeVar => "incomplete type var".to_owned(),
-    LexError::UnknownByte(b) => format!("unknown byte: {}", b),
-    LexError::InvalidIntConstant(e) => format!("invalid integer constant: {}", e),
-    LexError::InvalidRealConstant(e) => format!("invalid real constant: {}", e),
-    LexError::NegativeWordConstant => "negative word constant".to_owned(),
-    LexError::IncompleteNumConstant => "incomplete numeric constant".to_owned(),
-    LexError::UnclosedStringConstant => "unclosed string constant".to_owned(),
-    LexError::InvalidStringConstant => "invalid string constant".to_owned(),
-    LexError::InvalidCharConstant => "invalid character constant".to_owned(),
+/// Computes the code, location, message, and severity for a lex error. Pulled out of `lex` so
+/// that `millet-ls` can turn the same error into an LSP diagnostic without depending on
+/// `codespan_reporting`.
+pub fn describe_lex(err: &Located<LexError>) -> (&'static str, Loc, String, Severity) {
+  let (code, msg) = match err.val {
+    LexError::UnmatchedCloseComment => (
+      codes::E_LEX_UNMATCHED_CLOSE_COMMENT,
+      "unmatched close comment".to_owned(),
+    ),
+    LexError::UnmatchedOpenComment => (
+      codes::E_LEX_UNMATCHED_OPEN_COMMENT,
+      "unmatched open comment".to_owned(),
+    ),
+    LexError::IncompleteTypeVar => (
+      codes::E_LEX_INCOMPLETE_TYPE_VAR,
+      "incomplete type var".to_owned(),
+    ),
+    LexError::UnknownByte(b) => (codes::E_LEX_UNKNOWN_BYTE, format!("unknown byte: {}", b)),
+    LexError::InvalidIntConstant(e) => (
+      codes::E_LEX_INVALID_INT_CONSTANT,
+      format!("invalid integer constant: {}", e),
+    ),
+    LexError::InvalidRealConstant(e) => (
+      codes::E_LEX_INVALID_REAL_CONSTANT,
+      format!("invalid real constant: {}", e),
+    ),
+    LexError::NegativeWordConstant => (
+      codes::E_LEX_NEGATIVE_WORD_CONSTANT,
+      "negative word constant".to_owned(),
+    ),
+    LexError::IncompleteNumConstant => (
+      codes::E_LEX_INCOMPLETE_NUM_CONSTANT,
+      "incomplete numeric constant".to_owned(),
+    ),
+    LexError::UnclosedStringConstant => (
+      codes::E_LEX_UNCLOSED_STRING_CONSTANT,
+      "unclosed string constant".to_owned(),
+    ),
+    LexError::InvalidStringConstant => (
+      codes::E_LEX_INVALID_STRING_CONSTANT,
+      "invalid string constant".to_owned(),
+    ),
+    LexError::InvalidCharConstant => (
+      codes::E_LEX_INVALID_CHAR_CONSTANT,
+      "invalid character constant".to_owned(),
+    ),
   };
-  Diagnostic::error()
+  (code, err.loc, msg, Severity::Error)
+}
+
+pub fn lex(id: SourceId, err: Located<LexError>) -> Diagnostic {
+  let (code, loc, msg, severity) = describe_lex(&err);
+  Diagnostic::new(severity)
+    .with_code(code)
     .with_message(msg)
-    .with_labels(vec![Label::primary(id, err.loc)])
+    .with_labels(vec![Label::primary(id, loc)])
 }
 
-pub fn parse(store: &StrStore, id: SourceId, err: Located<ParseError>) -> Diagnostic {
-  let msg = match err.val {
-    ParseError::ExpectedButFound(exp, fnd) => format!("expected {}, found {}", exp, fnd),
-    ParseError::InfixWithoutOp(id) => format!(
-      "infix identifier used without preceding `op`: {}",
-      store.get(id)
-    ),
-    ParseError::NotInfix(id) => format!("non-infix identifier used as infix: {}", store.get(id)),
-    ParseError::RealPat => "real constant used as a pattern".to_owned(),
-    ParseError::NegativeFixity(n) => format!("fixity is negative: {}", n),
+/// Computes the code, location, message, and severity for a parse error. See `describe_lex`.
+pub fn describe_parse(
+  store: &StrStore,
+  err: &Located<ParseError>,
+) -> (&'static str, Loc, String, Severity) {
+  let (code, msg) = match err.val {
+    ParseError::ExpectedButFound(exp, fnd) => (
+      codes::E_PARSE_EXPECTED_BUT_FOUND,
+      format!("expected {}, found {}", exp, fnd),
+    ),
+    ParseError::InfixWithoutOp(id) => (
+      codes::E_PARSE_INFIX_WITHOUT_OP,
+      format!(
+        "infix identifier used without preceding `op`: {}",
+        store.get(id)
+      ),
+    ),
+    ParseError::NotInfix(id) => (
+      codes::E_PARSE_NOT_INFIX,
+      format!("non-infix identifier used as infix: {}", store.get(id)),
+    ),
+    ParseError::RealPat => (
+      codes::E_PARSE_REAL_PAT,
+      "real constant used as a pattern".to_owned(),
+    ),
+    ParseError::NegativeFixity(n) => (
+      codes::E_PARSE_NEGATIVE_FIXITY,
+      format!("fixity is negative: {}", n),
+    ),
   };
-  Diagnostic::error()
+  (code, err.loc, msg, Severity::Error)
+}
+
+pub fn parse(store: &StrStore, id: SourceId, err: Located<ParseError>) -> Diagnostic {
+  let (code, loc, msg, severity) = describe_parse(store, &err);
+  Diagnostic::new(severity)
+    .with_code(code)
     .with_message(msg)
-    .with_labels(vec![Label::primary(id, err.loc)])
+    .with_labels(vec![Label::primary(id, loc)])
 }
 
-pub fn statics(store: &StrStore, id: SourceId, err: StaticsError) -> Diagnostic {
-  let (loc, msg) = match err {
+/// A `Label::secondary` to attach alongside the primary label: the location it points at, and the
+/// message explaining what that location is.
+type Secondary = (Loc, String);
+
+/// Computes the code, location, message, severity, and secondary spans for a statics error. See
+/// `describe_lex`.
+pub fn describe_statics(
+  store: &StrStore,
+  err: StaticsError,
+) -> (&'static str, Loc, String, Severity, Vec<Secondary>) {
+  let (code, loc, msg, secondary) = match err {
     StaticsError::Undefined(item, id) => (
+      codes::E_STATICS_UNDEFINED,
       id.loc,
       format!("undefined {} identifier: {}", item, store.get(id.val)),
+      Vec::new(),
     ),
     StaticsError::Redefined(id) => (
+      codes::E_STATICS_REDEFINED,
       id.loc,
       format!("redefined identifier: {}", store.get(id.val)),
+      Vec::new(),
     ),
     StaticsError::DuplicateLabel(lab) => (
+      codes::E_STATICS_DUPLICATE_LABEL,
       lab.loc,
       format!("duplicate label: {}", show_lab(store, lab.val)),
+      Vec::new(),
     ),
-    StaticsError::Circularity(loc, ty_var, ty) => (
+    StaticsError::Circularity(loc, ty_var, ty, ty_loc) => (
+      codes::E_STATICS_CIRCULARITY,
       loc,
       format!("circularity: {} in {}", ty_var, show_ty(store, &ty)),
+      vec![(ty_loc, "the type originates from here".to_owned())],
     ),
-    StaticsError::HeadMismatch(loc, lhs, rhs) => (
+    StaticsError::HeadMismatch(loc, lhs, rhs, expected_loc, found_loc) => (
+      codes::E_STATICS_HEAD_MISMATCH,
       loc,
       format!(
         "mismatched types: {} vs {}",
         show_ty(store, &lhs),
         show_ty(store, &rhs)
       ),
+      vec![
+        (expected_loc, format!("expected {} from here", show_ty(store, &lhs))),
+        (found_loc, format!("found {} from here", show_ty(store, &rhs))),
+      ],
+    ),
+    StaticsError::MissingLabel(loc, lab, record_loc) => (
+      codes::E_STATICS_MISSING_LABEL,
+      loc,
+      format!("type is missing label {}", show_lab(store, lab)),
+      vec![(record_loc, "the record type originates from here".to_owned())],
     ),
-    StaticsError::MissingLabel(loc, lab) => (
+    StaticsError::ValAsPat(loc) => (
+      codes::E_STATICS_VAL_AS_PAT,
       loc,
-      format!("type is missing label {}", show_lab(store, lab),),
+      "value binding used as pattern".to_owned(),
+      Vec::new(),
     ),
-    StaticsError::ValAsPat(loc) => (loc, "value binding used as pattern".to_owned()),
     StaticsError::WrongNumTyArgs(loc, want, got) => (
+      codes::E_STATICS_WRONG_NUM_TY_ARGS,
       loc,
       format!(
         "wrong number of type arguments: expected {}, found {}",
         want, got
       ),
+      Vec::new(),
     ),
     StaticsError::NonVarInAs(name) => (
+      codes::E_STATICS_NON_VAR_IN_AS,
       name.loc,
       format!(
         "pattern to left of `as` is not a variable: {}",
         store.get(name.val)
       ),
+      Vec::new(),
     ),
     StaticsError::ForbiddenBinding(loc, name) => (
+      codes::E_STATICS_FORBIDDEN_BINDING,
       loc,
       format!("forbidden identifier in binding: {}", store.get(name)),
+      Vec::new(),
+    ),
+    StaticsError::NoSuitableOverload(loc) => (
+      codes::E_STATICS_NO_SUITABLE_OVERLOAD,
+      loc,
+      "no suitable overload found".to_owned(),
+      Vec::new(),
+    ),
+    StaticsError::Todo(loc) => (
+      codes::E_STATICS_TODO,
+      loc,
+      "unimplemented language construct".to_owned(),
+      Vec::new(),
     ),
-    StaticsError::NoSuitableOverload(loc) => (loc, "no suitable overload found".to_owned()),
-    StaticsError::Todo(loc) => (loc, "unimplemented language construct".to_owned()),
   };
-  Diagnostic::error()
+  (code, loc, msg, Severity::Error, secondary)
+}
+
+pub fn statics(store: &StrStore, id: SourceId, err: StaticsError) -> Diagnostic {
+  let (code, loc, msg, severity, secondary) = describe_statics(store, err);
+  let mut labels = vec![Label::primary(id, loc)];
+  labels.extend(
+    secondary
+      .into_iter()
+      .map(|(loc, msg): Secondary| Label::secondary(id, loc).with_message(msg)),
+  );
+  Diagnostic::new(severity)
+    .with_code(code)
     .with_message(msg)
-    .with_labels(vec![Label::primary(id, loc)])
+    .with_labels(labels)
 }
 
 fn show_lab(store: &StrStore, lab: AstLabel) -> String {
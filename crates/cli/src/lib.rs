@@ -0,0 +1,10 @@
+//! The library surface backing the `millet` CLI binary.
+//!
+//! This is split out from `main.rs` so that other frontends over the same lex/parse/statics
+//! pipeline, like `millet-ls`, can reuse the diagnostic formatting in `diagnostic.rs` instead of
+//! duplicating it.
+
+pub mod args;
+pub mod codes;
+pub mod diagnostic;
+pub mod source;
@@ -1,58 +1,100 @@
 //! A CLI for millet.
 
-mod args;
-mod diagnostic;
-mod source;
-
 use codespan_reporting::term;
 use codespan_reporting::term::termcolor::{ColorChoice, StandardStream};
-use millet_core::{error, lex, parse};
+use millet_cli::args::{self, ErrorFormat};
+use millet_cli::{diagnostic, source};
+use millet_core::{lex, parse};
 use std::io::Write as _;
 
+fn emit(
+  writer: &mut impl std::io::Write,
+  config: &term::Config,
+  source_map: &source::SourceMap,
+  error_format: ErrorFormat,
+  diag: &diagnostic::Diagnostic,
+) {
+  match error_format {
+    ErrorFormat::Human => term::emit(writer, config, source_map, diag).unwrap(),
+    ErrorFormat::Json => diagnostic::emit_json(writer, source_map, diag).unwrap(),
+  }
+}
+
+/// Runs every file in `args::get().files` through lex and parse, reporting every lex and parse
+/// error found in each file -- not just the first.
+///
+/// This relies on `lex::get` and `parse::get` themselves recovering instead of stopping at the
+/// first problem: `lex::get` skips past a bad token and keeps lexing, and `parse::get` resyncs to
+/// the next declaration boundary (or a top-level `;`) after a syntax error and keeps parsing. A
+/// file with several unrelated mistakes therefore reports all of them in one pass, the same as a
+/// project with several broken files does.
+///
+/// Statics is not run here at all: this checkout's `statics::ck` implements signature matching
+/// (`sig_match`, `enrich`) over an already-built `Env`/`Sig`, but there is no elaborator that turns
+/// a parsed program into one, so there is nothing for `run` to call yet.
 fn run() -> bool {
   let args = args::get();
+  if let Some(code) = args.explain {
+    return explain(&code);
+  }
   let config = term::Config::default();
-  let writer = StandardStream::stdout(ColorChoice::Auto);
-  let mut writer = writer.lock();
+  let stdout = StandardStream::stdout(ColorChoice::Auto);
+  let mut writer = stdout.lock();
   let mut source_map = source::SourceMap::new();
+  let mut ok = true;
   for name in args.files {
     match std::fs::read_to_string(&name) {
-      Ok(s) => source_map.insert(name, s),
+      Ok(s) => {
+        source_map.insert(name, s);
+      }
       Err(e) => {
         writeln!(writer, "io error: {}: {}", name, e).unwrap();
-        return false;
+        ok = false;
       }
     }
   }
   for (id, file) in source_map.iter() {
-    let lexer = match lex::get(file.as_bytes()) {
-      Ok(x) => x,
-      Err(e) => {
-        term::emit(
-          &mut writer,
-          &config,
-          &source_map,
-          &diagnostic::new(id, e.loc.wrap(error::Error::Lex(e.val))),
-        )
-        .unwrap();
-        return false;
-      }
-    };
-    match parse::get(lexer) {
-      Ok(xs) => eprintln!("parsed: {:#?}", xs),
-      Err(e) => {
-        term::emit(
-          &mut writer,
-          &config,
-          &source_map,
-          &diagnostic::new(id, e.loc.wrap(error::Error::Parse(e.val))),
-        )
-        .unwrap();
-        return false;
-      }
+    let lexer = lex::get(file.as_bytes());
+    for err in lexer.errors() {
+      emit(
+        &mut writer,
+        &config,
+        &source_map,
+        args.error_format,
+        &diagnostic::lex(id, *err),
+      );
+      ok = false;
+    }
+    let parsed = parse::get(lexer);
+    for err in parsed.errors() {
+      emit(
+        &mut writer,
+        &config,
+        &source_map,
+        args.error_format,
+        &diagnostic::parse(parsed.str_store(), id, *err),
+      );
+      ok = false;
+    }
+    if parsed.errors().is_empty() {
+      eprintln!("parsed: {:#?}", parsed.decs());
+    }
+  }
+  ok
+}
+
+/// Prints the extended explanation for `code` and returns whether `code` was recognized.
+fn explain(code: &str) -> bool {
+  match millet_cli::codes::explain(code) {
+    Some(text) => {
+      println!("{}", text);
+      true
+    }
+    None => {
+      eprintln!("unknown code: {}", code);
+      false
     }
   }
-  true
 }
 
 fn main() {
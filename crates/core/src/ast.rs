@@ -0,0 +1,10 @@
+//! Abstract syntax tree node kinds shared across the parser and statics checker.
+
+use crate::intern::StrRef;
+
+/// A record label: either a name (`foo`) or a positional tuple index (`1`, `2`, ...).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Label {
+  Vid(StrRef),
+  Num(u32),
+}
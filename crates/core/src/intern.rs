@@ -7,19 +7,49 @@ use std::collections::HashMap;
 #[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
 pub struct StrRef(usize);
 
-// TODO generate this with a macro?
-impl StrRef {
-  pub const STAR: StrRef = StrRef(0);
-  pub const INT: StrRef = StrRef(1);
-  pub const REAL: StrRef = StrRef(2);
-  pub const WORD: StrRef = StrRef(3);
-  pub const CHAR: StrRef = StrRef(4);
-  pub const STRING: StrRef = StrRef(5);
-  pub const LIST: StrRef = StrRef(6);
-  pub const NIL: StrRef = StrRef(7);
-  pub const CONS: StrRef = StrRef(8);
-  pub const TRUE: StrRef = StrRef(9);
-  pub const FALSE: StrRef = StrRef(10);
+/// Declares the builtin `StrRef`s and the strings they represent.
+///
+/// Expands to the `StrRef` associated consts (assigned indices in declaration order), a
+/// `prefill` function that inserts each string into a fresh map at its corresponding index, and
+/// a `NUM_BUILTINS` count. This keeps the invariant "index equals the const's id" enforced by
+/// construction, rather than by keeping two hand-written lists in sync.
+macro_rules! builtin_strs {
+  ($($name:ident => $str:expr,)*) => {
+    impl StrRef {
+      builtin_strs!(@consts 0; $($name => $str,)*);
+    }
+
+    /// The number of builtin `StrRef`s declared by `builtin_strs!`.
+    const NUM_BUILTINS: usize = builtin_strs!(@count $($name),*);
+
+    /// Inserts every builtin string into `store` at its builtin `StrRef`'s index.
+    fn prefill(store: &mut HashMap<String, StrRef>) {
+      $(store.insert($str.to_owned(), StrRef::$name);)*
+    }
+  };
+  (@consts $idx:expr; $name:ident => $str:expr, $($rest:tt)*) => {
+    pub const $name: StrRef = StrRef($idx);
+    builtin_strs!(@consts $idx + 1; $($rest)*);
+  };
+  (@consts $idx:expr;) => {};
+  (@count $($name:ident),*) => {
+    <[()]>::len(&[$(builtin_strs!(@unit $name)),*])
+  };
+  (@unit $name:ident) => { () };
+}
+
+builtin_strs! {
+  STAR => "*",
+  INT => "int",
+  REAL => "real",
+  WORD => "word",
+  CHAR => "char",
+  STRING => "string",
+  LIST => "list",
+  NIL => "nil",
+  CONS => "::",
+  TRUE => "true",
+  FALSE => "false",
 }
 
 /// A mutable factory of StrRefs. Allows creating new StrRefs from Strings.
@@ -31,18 +61,8 @@ pub struct StrStoreMut {
 impl StrStoreMut {
   /// Returns an new StrStoreMut containing only the special StrRefs.
   pub fn new() -> Self {
-    let mut store = HashMap::with_capacity(11);
-    store.insert("*".to_owned(), StrRef::STAR);
-    store.insert("int".to_owned(), StrRef::INT);
-    store.insert("real".to_owned(), StrRef::REAL);
-    store.insert("word".to_owned(), StrRef::WORD);
-    store.insert("char".to_owned(), StrRef::CHAR);
-    store.insert("string".to_owned(), StrRef::STRING);
-    store.insert("list".to_owned(), StrRef::LIST);
-    store.insert("nil".to_owned(), StrRef::NIL);
-    store.insert("::".to_owned(), StrRef::CONS);
-    store.insert("true".to_owned(), StrRef::TRUE);
-    store.insert("false".to_owned(), StrRef::FALSE);
+    let mut store = HashMap::with_capacity(NUM_BUILTINS);
+    prefill(&mut store);
     Self {
       next: store.len(),
       store,
@@ -94,3 +114,46 @@ impl StrStore {
     self.store[id.0].as_str()
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn consts_are_assigned_by_declaration_order() {
+    assert_eq!(StrRef::STAR, StrRef(0));
+    assert_eq!(StrRef::INT, StrRef(1));
+    assert_eq!(StrRef::REAL, StrRef(2));
+    assert_eq!(StrRef::WORD, StrRef(3));
+    assert_eq!(StrRef::CHAR, StrRef(4));
+    assert_eq!(StrRef::STRING, StrRef(5));
+    assert_eq!(StrRef::LIST, StrRef(6));
+    assert_eq!(StrRef::NIL, StrRef(7));
+    assert_eq!(StrRef::CONS, StrRef(8));
+    assert_eq!(StrRef::TRUE, StrRef(9));
+    assert_eq!(StrRef::FALSE, StrRef(10));
+  }
+
+  #[test]
+  fn num_builtins_matches_the_number_of_consts_declared() {
+    assert_eq!(NUM_BUILTINS, 11);
+  }
+
+  #[test]
+  fn prefill_resolves_every_builtin_back_to_its_string() {
+    let store = StrStoreMut::new().finish();
+    assert_eq!(store.get(StrRef::STAR), "*");
+    assert_eq!(store.get(StrRef::INT), "int");
+    assert_eq!(store.get(StrRef::CONS), "::");
+    assert_eq!(store.get(StrRef::FALSE), "false");
+  }
+
+  #[test]
+  fn new_strings_are_interned_after_the_builtins() {
+    let mut store = StrStoreMut::new();
+    let foo = store.insert_str("foo");
+    assert_eq!(foo, StrRef(NUM_BUILTINS));
+    // interning the same string twice returns the same StrRef, not a fresh one.
+    assert_eq!(store.insert_str("foo"), foo);
+  }
+}
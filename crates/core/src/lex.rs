@@ -0,0 +1,480 @@
+//! Lexing: turns a file's source text into a stream of tokens.
+//!
+//! A lexical mistake does not stop lexing: `get` skips past it and keeps going, recording a
+//! `LexError` for each one it finds instead of bailing at the first. A file with several unrelated
+//! lexical mistakes therefore reports all of them, not just the first -- see `Lexer::errors`.
+
+use crate::intern::{StrRef, StrStore, StrStoreMut};
+use crate::loc::{Loc, Located};
+
+/// A lexical token. Offsets into the source are attached by the `Located` wrapper around this, not
+/// stored here.
+#[derive(Debug, Clone, Copy)]
+pub enum Token {
+  Ident(StrRef),
+  Symbolic(StrRef),
+  IntLit(i64),
+  RealLit(f64),
+  WordLit(u64),
+  CharLit(char),
+  StringLit(StrRef),
+  LParen,
+  RParen,
+  Comma,
+  Semicolon,
+  Eq,
+  Arrow,
+  Kw(Kw),
+  /// Emitted once, at the end of the token stream, so parsing never has to special-case running
+  /// off the end.
+  Eof,
+}
+
+/// The reserved words this lexer recognizes. Not exhaustive over all of Standard ML's reserved
+/// words -- only the ones `parse` needs to find declaration boundaries and recognize the
+/// declaration forms it implements.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kw {
+  Val,
+  Fun,
+  Datatype,
+  Type,
+  Structure,
+  Signature,
+  Functor,
+  Exception,
+  Open,
+  Local,
+  In,
+  End,
+  Infix,
+  Infixr,
+  Nonfix,
+  And,
+  Op,
+}
+
+impl Kw {
+  fn from_str(s: &str) -> Option<Self> {
+    Some(match s {
+      "val" => Self::Val,
+      "fun" => Self::Fun,
+      "datatype" => Self::Datatype,
+      "type" => Self::Type,
+      "structure" => Self::Structure,
+      "signature" => Self::Signature,
+      "functor" => Self::Functor,
+      "exception" => Self::Exception,
+      "open" => Self::Open,
+      "local" => Self::Local,
+      "in" => Self::In,
+      "end" => Self::End,
+      "infix" => Self::Infix,
+      "infixr" => Self::Infixr,
+      "nonfix" => Self::Nonfix,
+      "and" => Self::And,
+      "op" => Self::Op,
+      _ => return None,
+    })
+  }
+
+  /// Whether this keyword starts a new top-level declaration. `parse`'s error recovery skips
+  /// forward to the next token for which this is true (or to a top-level `;`).
+  pub fn starts_dec(self) -> bool {
+    !matches!(self, Self::In | Self::End | Self::And | Self::Op)
+  }
+}
+
+/// An error found while lexing.
+#[derive(Debug, Clone, Copy)]
+pub enum LexError {
+  UnmatchedCloseComment,
+  UnmatchedOpenComment,
+  IncompleteTypeVar,
+  UnknownByte(u8),
+  InvalidIntConstant(&'static str),
+  InvalidRealConstant(&'static str),
+  NegativeWordConstant,
+  IncompleteNumConstant,
+  UnclosedStringConstant,
+  InvalidStringConstant,
+  InvalidCharConstant,
+}
+
+/// The result of lexing: a token stream (always produced, even in the presence of errors) and the
+/// errors found along the way.
+pub struct Lexer {
+  tokens: Vec<Located<Token>>,
+  errors: Vec<Located<LexError>>,
+  store: StrStore,
+}
+
+impl Lexer {
+  pub fn tokens(&self) -> &[Located<Token>] {
+    &self.tokens
+  }
+
+  pub fn errors(&self) -> &[Located<LexError>] {
+    &self.errors
+  }
+
+  pub fn str_store(&self) -> &StrStore {
+    &self.store
+  }
+
+  /// Consumes this `Lexer`, handing back its token stream and the interner it filled in along the
+  /// way. Used by `parse::get`, which needs to own both.
+  pub fn into_parts(self) -> (Vec<Located<Token>>, StrStore) {
+    (self.tokens, self.store)
+  }
+}
+
+/// Lexes `src` into a token stream, recovering from each lexical error instead of stopping at it.
+pub fn get(src: &[u8]) -> Lexer {
+  let text = String::from_utf8_lossy(src);
+  let mut cx = Cx {
+    chars: text.char_indices().collect(),
+    idx: 0,
+    len: text.len(),
+    tokens: Vec::new(),
+    errors: Vec::new(),
+    store: StrStoreMut::new(),
+  };
+  cx.run();
+  Lexer {
+    tokens: cx.tokens,
+    errors: cx.errors,
+    store: cx.store.finish(),
+  }
+}
+
+struct Cx {
+  chars: Vec<(usize, char)>,
+  idx: usize,
+  len: usize,
+  tokens: Vec<Located<Token>>,
+  errors: Vec<Located<LexError>>,
+  store: StrStoreMut,
+}
+
+fn is_sym_char(c: char) -> bool {
+  matches!(
+    c,
+    '!' | '%' | '&' | '$' | '#' | '+' | '-' | '/' | ':' | '<' | '=' | '>' | '?' | '@' | '\\' | '~'
+      | '^' | '|' | '*'
+  )
+}
+
+impl Cx {
+  fn byte_pos(&self, idx: usize) -> usize {
+    self.chars.get(idx).map_or(self.len, |&(b, _)| b)
+  }
+
+  fn peek(&self) -> Option<char> {
+    self.chars.get(self.idx).map(|&(_, c)| c)
+  }
+
+  fn peek_at(&self, offset: usize) -> Option<char> {
+    self.chars.get(self.idx + offset).map(|&(_, c)| c)
+  }
+
+  fn bump(&mut self) -> Option<char> {
+    let c = self.peek()?;
+    self.idx += 1;
+    Some(c)
+  }
+
+  fn run(&mut self) {
+    loop {
+      self.skip_trivia();
+      let start_idx = self.idx;
+      let start = self.byte_pos(start_idx);
+      let Some(c) = self.peek() else { break };
+      let tok = if c.is_ascii_digit() {
+        self.number()
+      } else if c == '~' && self.peek_at(1).is_some_and(|c| c.is_ascii_digit()) {
+        self.number()
+      } else if c == '"' {
+        self.string()
+      } else if c == '#' && self.peek_at(1) == Some('"') {
+        self.bump();
+        self.char_lit()
+      } else if c == '\'' {
+        self.ty_var()
+      } else if c.is_alphabetic() || c == '_' {
+        self.ident()
+      } else if is_sym_char(c) {
+        self.symbolic()
+      } else {
+        self.bump();
+        match c {
+          '(' => Some(Token::LParen),
+          ')' => Some(Token::RParen),
+          ',' => Some(Token::Comma),
+          ';' => Some(Token::Semicolon),
+          _ => {
+            let mut buf = [0u8; 4];
+            let byte = c.encode_utf8(&mut buf).as_bytes()[0];
+            self.error_at(start, self.byte_pos(self.idx), LexError::UnknownByte(byte));
+            None
+          }
+        }
+      };
+      if let Some(tok) = tok {
+        let end = self.byte_pos(self.idx);
+        self.tokens.push(Loc::new(start, end).wrap(tok));
+      }
+    }
+    let eof = self.byte_pos(self.idx);
+    self.tokens.push(Loc::new(eof, eof).wrap(Token::Eof));
+  }
+
+  fn error_at(&mut self, start: usize, end: usize, err: LexError) {
+    self.errors.push(Loc::new(start, end).wrap(err));
+  }
+
+  /// Skips whitespace and `(* ... *)` comments (which nest), recovering from an unmatched `*)` or
+  /// an unmatched `(*` by recording an error and continuing past it.
+  fn skip_trivia(&mut self) {
+    loop {
+      match self.peek() {
+        Some(c) if c.is_whitespace() => {
+          self.bump();
+        }
+        Some('(') if self.peek_at(1) == Some('*') => {
+          let start = self.byte_pos(self.idx);
+          self.bump();
+          self.bump();
+          let mut depth = 1usize;
+          while depth > 0 {
+            match self.peek() {
+              None => {
+                self.error_at(start, self.byte_pos(self.idx), LexError::UnmatchedOpenComment);
+                return;
+              }
+              Some('(') if self.peek_at(1) == Some('*') => {
+                self.bump();
+                self.bump();
+                depth += 1;
+              }
+              Some('*') if self.peek_at(1) == Some(')') => {
+                self.bump();
+                self.bump();
+                depth -= 1;
+              }
+              Some(_) => {
+                self.bump();
+              }
+            }
+          }
+        }
+        Some('*') if self.peek_at(1) == Some(')') => {
+          let start = self.byte_pos(self.idx);
+          self.bump();
+          self.bump();
+          self.error_at(start, self.byte_pos(self.idx), LexError::UnmatchedCloseComment);
+        }
+        _ => return,
+      }
+    }
+  }
+
+  fn ident(&mut self) -> Option<Token> {
+    let start = self.idx;
+    while self
+      .peek()
+      .is_some_and(|c| c.is_alphanumeric() || c == '_' || c == '\'')
+    {
+      self.bump();
+    }
+    let s: String = self.chars[start..self.idx].iter().map(|&(_, c)| c).collect();
+    if let Some(kw) = Kw::from_str(&s) {
+      Some(Token::Kw(kw))
+    } else {
+      Some(Token::Ident(self.store.insert_string(s)))
+    }
+  }
+
+  fn symbolic(&mut self) -> Option<Token> {
+    let start = self.idx;
+    while self.peek().is_some_and(is_sym_char) {
+      self.bump();
+    }
+    let s: String = self.chars[start..self.idx].iter().map(|&(_, c)| c).collect();
+    match s.as_str() {
+      "=" => Some(Token::Eq),
+      "->" => Some(Token::Arrow),
+      _ => Some(Token::Symbolic(self.store.insert_string(s))),
+    }
+  }
+
+  fn ty_var(&mut self) -> Option<Token> {
+    let start_idx = self.idx;
+    let start = self.byte_pos(start_idx);
+    self.bump(); // the leading `'`
+    while self.peek() == Some('\'') {
+      self.bump();
+    }
+    let name_start = self.idx;
+    while self
+      .peek()
+      .is_some_and(|c| c.is_alphanumeric() || c == '_')
+    {
+      self.bump();
+    }
+    if self.idx == name_start {
+      self.error_at(start, self.byte_pos(self.idx), LexError::IncompleteTypeVar);
+      return None;
+    }
+    let s: String = self.chars[start_idx..self.idx].iter().map(|&(_, c)| c).collect();
+    Some(Token::Ident(self.store.insert_string(s)))
+  }
+
+  fn number(&mut self) -> Option<Token> {
+    let start_idx = self.idx;
+    let start = self.byte_pos(start_idx);
+    let negative = self.peek() == Some('~');
+    if negative {
+      self.bump();
+    }
+    if self.peek() == Some('0') && self.peek_at(1) == Some('w') {
+      self.bump();
+      self.bump();
+      if negative {
+        self.error_at(start, self.byte_pos(self.idx), LexError::NegativeWordConstant);
+        return None;
+      }
+      let digits_start = self.idx;
+      while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+        self.bump();
+      }
+      if self.idx == digits_start {
+        self.error_at(start, self.byte_pos(self.idx), LexError::IncompleteNumConstant);
+        return None;
+      }
+      let s: String = self.chars[digits_start..self.idx].iter().map(|&(_, c)| c).collect();
+      return match s.parse::<u64>() {
+        Ok(n) => Some(Token::WordLit(n)),
+        Err(_) => {
+          self.error_at(start, self.byte_pos(self.idx), LexError::InvalidIntConstant("too large to fit in 64 bits"));
+          None
+        }
+      };
+    }
+    let int_start = self.idx;
+    while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+      self.bump();
+    }
+    let mut is_real = false;
+    if self.peek() == Some('.') {
+      if !self.peek_at(1).is_some_and(|c| c.is_ascii_digit()) {
+        self.bump();
+        self.error_at(start, self.byte_pos(self.idx), LexError::InvalidRealConstant("no digits after the decimal point"));
+        return None;
+      }
+      is_real = true;
+      self.bump();
+      while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+        self.bump();
+      }
+    }
+    if matches!(self.peek(), Some('e') | Some('E')) {
+      self.bump();
+      if self.peek() == Some('~') {
+        self.bump();
+      }
+      let exp_digits_start = self.idx;
+      while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+        self.bump();
+      }
+      if self.idx == exp_digits_start {
+        self.error_at(start, self.byte_pos(self.idx), LexError::IncompleteNumConstant);
+        return None;
+      }
+      is_real = true;
+    }
+    let s: String = self.chars[int_start..self.idx].iter().map(|&(_, c)| c).collect();
+    if is_real {
+      let full: String = self.chars[start_idx..self.idx]
+        .iter()
+        .map(|&(_, c)| if c == '~' { '-' } else { c })
+        .collect();
+      match full.parse::<f64>() {
+        Ok(n) => Some(Token::RealLit(n)),
+        Err(_) => {
+          self.error_at(start, self.byte_pos(self.idx), LexError::InvalidRealConstant("could not be parsed"));
+          None
+        }
+      }
+    } else {
+      match s.parse::<i64>() {
+        Ok(n) => Some(Token::IntLit(if negative { -n } else { n })),
+        Err(_) => {
+          self.error_at(start, self.byte_pos(self.idx), LexError::InvalidIntConstant("too large to fit in 64 bits"));
+          None
+        }
+      }
+    }
+  }
+
+  fn string(&mut self) -> Option<Token> {
+    let start = self.byte_pos(self.idx);
+    self.bump(); // opening quote
+    let mut buf = String::new();
+    let mut ok = true;
+    loop {
+      match self.peek() {
+        None | Some('\n') => {
+          self.error_at(start, self.byte_pos(self.idx), LexError::UnclosedStringConstant);
+          return None;
+        }
+        Some('"') => {
+          self.bump();
+          break;
+        }
+        Some('\\') => {
+          self.bump();
+          match self.bump() {
+            Some('n') => buf.push('\n'),
+            Some('t') => buf.push('\t'),
+            Some('\\') => buf.push('\\'),
+            Some('"') => buf.push('"'),
+            _ => ok = false,
+          }
+        }
+        Some(c) => {
+          self.bump();
+          buf.push(c);
+        }
+      }
+    }
+    if !ok {
+      self.error_at(start, self.byte_pos(self.idx), LexError::InvalidStringConstant);
+      return None;
+    }
+    Some(Token::StringLit(self.store.insert_string(buf)))
+  }
+
+  fn char_lit(&mut self) -> Option<Token> {
+    let start = self.byte_pos(self.idx) - 1; // back up over the `#`
+    self.bump(); // opening quote
+    let c = self.bump();
+    let closed = self.peek() == Some('"');
+    if closed {
+      self.bump();
+    }
+    match (c, closed) {
+      (Some(c), true) => Some(Token::CharLit(c)),
+      _ => {
+        while self.peek().is_some_and(|c| c != '"' && c != '\n') {
+          self.bump();
+        }
+        if self.peek() == Some('"') {
+          self.bump();
+        }
+        self.error_at(start, self.byte_pos(self.idx), LexError::InvalidCharConstant);
+        None
+      }
+    }
+  }
+}
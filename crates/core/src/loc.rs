@@ -0,0 +1,32 @@
+//! Byte-offset source locations.
+
+/// A half-open byte-offset span `[start, end)` into a single source file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Loc {
+  pub start: usize,
+  pub end: usize,
+}
+
+impl Loc {
+  pub fn new(start: usize, end: usize) -> Self {
+    Self { start, end }
+  }
+
+  /// Pairs `val` with this location.
+  pub fn wrap<T>(self, val: T) -> Located<T> {
+    Located { loc: self, val }
+  }
+}
+
+impl From<Loc> for std::ops::Range<usize> {
+  fn from(loc: Loc) -> Self {
+    loc.start..loc.end
+  }
+}
+
+/// A value paired with the source location it came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Located<T> {
+  pub loc: Loc,
+  pub val: T,
+}
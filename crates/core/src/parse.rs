@@ -0,0 +1,366 @@
+//! Parsing: turns a token stream into a sequence of top-level declarations.
+//!
+//! A syntax error does not stop parsing: `get` records a `ParseError` for it and then resyncs by
+//! skipping tokens until the next declaration-boundary keyword (`val`, `fun`, `datatype`, ...) or a
+//! top-level `;`, then keeps parsing from there. A file with several unrelated syntax mistakes thus
+//! reports all of them, not just the first -- see `Parse::errors`.
+
+use crate::intern::{StrRef, StrStore};
+use crate::lex::{Kw, Lexer, Token};
+use crate::loc::{Loc, Located};
+use std::collections::HashMap;
+
+/// A pattern, as reduced as `parse`'s grammar: a wildcard, a variable, or an integer literal.
+#[derive(Debug)]
+pub enum Pat {
+  Wild,
+  Var(StrRef),
+  Int(i64),
+}
+
+/// An expression, as reduced as `parse`'s grammar.
+#[derive(Debug)]
+pub enum Exp {
+  Var(StrRef),
+  Int(i64),
+  Real(f64),
+  Word(u64),
+  Char(char),
+  String(StrRef),
+  Paren(Box<Exp>),
+  Infix(Box<Exp>, StrRef, Box<Exp>),
+}
+
+/// A top-level declaration.
+#[derive(Debug)]
+pub enum Dec {
+  Val(Pat, Exp),
+  /// An `infix`/`infixr`/`nonfix` declaration. Its only effect is updating the parser's fixity
+  /// table while parsing, so there is nothing further to record about it here.
+  Fixity,
+  /// A declaration `parse` recognized the start of (a keyword it knows is a declaration-boundary
+  /// keyword) but does not implement further, e.g. `fun`, `datatype`, `structure`. Standing in for
+  /// the elaboration that would otherwise turn this into a real `Dec` -- see the `StaticsError::
+  /// Todo` this is the parse-level counterpart of.
+  Unimplemented,
+  /// Recovery placeholder for a declaration that failed to parse. The `Loc` is the span that was
+  /// skipped resyncing to the next declaration boundary.
+  Error(Loc),
+}
+
+/// An error found while parsing.
+#[derive(Debug, Clone, Copy)]
+pub enum ParseError {
+  ExpectedButFound(&'static str, &'static str),
+  InfixWithoutOp(StrRef),
+  NotInfix(StrRef),
+  RealPat,
+  NegativeFixity(i32),
+}
+
+type Result<T> = std::result::Result<T, Located<ParseError>>;
+
+/// The result of parsing: the declarations recovered (including placeholders where recovery had to
+/// kick in) and the errors found along the way.
+pub struct Parse {
+  decs: Vec<Dec>,
+  errors: Vec<Located<ParseError>>,
+  store: StrStore,
+}
+
+impl Parse {
+  pub fn decs(&self) -> &[Dec] {
+    &self.decs
+  }
+
+  pub fn errors(&self) -> &[Located<ParseError>] {
+    &self.errors
+  }
+
+  pub fn str_store(&self) -> &StrStore {
+    &self.store
+  }
+}
+
+#[derive(Clone, Copy)]
+struct Fixity {
+  level: u32,
+  right_assoc: bool,
+}
+
+struct Parser<'s> {
+  tokens: Vec<Located<Token>>,
+  pos: usize,
+  errors: Vec<Located<ParseError>>,
+  fixities: HashMap<StrRef, Fixity>,
+  store: &'s StrStore,
+}
+
+/// Parses `lexer`'s token stream into a `Parse`, recovering from each syntax error instead of
+/// stopping at it.
+pub fn get(lexer: Lexer) -> Parse {
+  let (tokens, store) = lexer.into_parts();
+  let mut p = Parser {
+    tokens,
+    pos: 0,
+    errors: Vec::new(),
+    fixities: HashMap::new(),
+    store: &store,
+  };
+  let mut decs = Vec::new();
+  while !p.at_eof() {
+    if p.eat_semicolon() {
+      continue;
+    }
+    match p.dec() {
+      Ok(dec) => decs.push(dec),
+      Err(e) => {
+        let start = p.loc().start;
+        p.errors.push(e);
+        p.recover();
+        decs.push(Dec::Error(Loc::new(start, p.loc().start)));
+      }
+    }
+  }
+  let errors = p.errors;
+  Parse {
+    decs,
+    errors,
+    store,
+  }
+}
+
+impl<'s> Parser<'s> {
+  fn tok(&self) -> Token {
+    self.tokens[self.pos].val
+  }
+
+  fn loc(&self) -> Loc {
+    self.tokens[self.pos].loc
+  }
+
+  fn at_eof(&self) -> bool {
+    matches!(self.tok(), Token::Eof)
+  }
+
+  fn bump(&mut self) -> Located<Token> {
+    let t = self.tokens[self.pos];
+    if !matches!(t.val, Token::Eof) {
+      self.pos += 1;
+    }
+    t
+  }
+
+  fn eat_semicolon(&mut self) -> bool {
+    if matches!(self.tok(), Token::Semicolon) {
+      self.bump();
+      true
+    } else {
+      false
+    }
+  }
+
+  /// Skips tokens until the next declaration-boundary keyword, a top-level `;`, or the end of the
+  /// file -- used to resync after a parse error so the rest of the file still gets parsed.
+  fn recover(&mut self) {
+    loop {
+      match self.tok() {
+        Token::Eof | Token::Semicolon => return,
+        Token::Kw(kw) if kw.starts_dec() => return,
+        _ => {
+          self.bump();
+        }
+      }
+    }
+  }
+
+  fn desc(tok: Token) -> &'static str {
+    match tok {
+      Token::Ident(_) => "an identifier",
+      Token::Symbolic(_) => "an operator",
+      Token::IntLit(_) | Token::RealLit(_) | Token::WordLit(_) => "a number",
+      Token::CharLit(_) => "a character constant",
+      Token::StringLit(_) => "a string constant",
+      Token::LParen => "`(`",
+      Token::RParen => "`)`",
+      Token::Comma => "`,`",
+      Token::Semicolon => "`;`",
+      Token::Eq => "`=`",
+      Token::Arrow => "`->`",
+      Token::Kw(_) => "a keyword",
+      Token::Eof => "end of input",
+    }
+  }
+
+  fn expect(&mut self, tok: Token, what: &'static str) -> Result<()> {
+    if std::mem::discriminant(&self.tok()) == std::mem::discriminant(&tok) {
+      self.bump();
+      Ok(())
+    } else {
+      Err(self.loc().wrap(ParseError::ExpectedButFound(what, Self::desc(self.tok()))))
+    }
+  }
+
+  fn dec(&mut self) -> Result<Dec> {
+    match self.tok() {
+      Token::Kw(Kw::Val) => {
+        self.bump();
+        let pat = self.pat()?;
+        self.expect(Token::Eq, "`=`")?;
+        let exp = self.exp()?;
+        Ok(Dec::Val(pat, exp))
+      }
+      Token::Kw(Kw::Infix) | Token::Kw(Kw::Infixr) | Token::Kw(Kw::Nonfix) => self.fixity_dec(),
+      Token::Kw(kw) if kw.starts_dec() => {
+        self.bump();
+        self.recover();
+        Ok(Dec::Unimplemented)
+      }
+      other => Err(self.loc().wrap(ParseError::ExpectedButFound("a declaration", Self::desc(other)))),
+    }
+  }
+
+  fn fixity_dec(&mut self) -> Result<Dec> {
+    let right_assoc = matches!(self.tok(), Token::Kw(Kw::Infixr));
+    let nonfix = matches!(self.tok(), Token::Kw(Kw::Nonfix));
+    self.bump();
+    let mut level = 0u32;
+    if !nonfix {
+      if matches!(self.tok(), Token::Symbolic(s) if self.store.get(s) == "~") {
+        let neg_loc = self.loc();
+        self.bump();
+        let n = match self.tok() {
+          Token::IntLit(n) => {
+            self.bump();
+            n
+          }
+          _ => 0,
+        };
+        return Err(neg_loc.wrap(ParseError::NegativeFixity(-(n as i32))));
+      }
+      if let Token::IntLit(n) = self.tok() {
+        let loc = self.loc();
+        self.bump();
+        // the lexer folds `~1` into a single negative integer literal.
+        if n < 0 {
+          return Err(loc.wrap(ParseError::NegativeFixity(n as i32)));
+        }
+        level = n as u32;
+      }
+    }
+    let mut saw_one = false;
+    loop {
+      let name = match self.tok() {
+        Token::Ident(s) | Token::Symbolic(s) => s,
+        _ => break,
+      };
+      self.bump();
+      saw_one = true;
+      if nonfix {
+        self.fixities.remove(&name);
+      } else {
+        self.fixities.insert(name, Fixity { level, right_assoc });
+      }
+    }
+    if !saw_one {
+      return Err(self.loc().wrap(ParseError::ExpectedButFound(
+        "an identifier",
+        Self::desc(self.tok()),
+      )));
+    }
+    Ok(Dec::Fixity)
+  }
+
+  fn pat(&mut self) -> Result<Pat> {
+    match self.tok() {
+      Token::Ident(s) if self.store.get(s) == "_" => {
+        self.bump();
+        Ok(Pat::Wild)
+      }
+      Token::Ident(s) => {
+        self.bump();
+        Ok(Pat::Var(s))
+      }
+      Token::IntLit(n) => {
+        self.bump();
+        Ok(Pat::Int(n))
+      }
+      Token::RealLit(_) => {
+        let loc = self.loc();
+        self.bump();
+        Err(loc.wrap(ParseError::RealPat))
+      }
+      other => Err(self.loc().wrap(ParseError::ExpectedButFound("a pattern", Self::desc(other)))),
+    }
+  }
+
+  fn atom_exp(&mut self) -> Result<Exp> {
+    match self.tok() {
+      Token::Kw(Kw::Op) => {
+        self.bump();
+        match self.tok() {
+          Token::Ident(s) | Token::Symbolic(s) => {
+            self.bump();
+            Ok(Exp::Var(s))
+          }
+          other => Err(self.loc().wrap(ParseError::ExpectedButFound(
+            "an identifier",
+            Self::desc(other),
+          ))),
+        }
+      }
+      Token::Ident(s) | Token::Symbolic(s) if self.fixities.contains_key(&s) => {
+        let loc = self.loc();
+        Err(loc.wrap(ParseError::InfixWithoutOp(s)))
+      }
+      Token::Ident(s) => {
+        self.bump();
+        Ok(Exp::Var(s))
+      }
+      Token::IntLit(n) => {
+        self.bump();
+        Ok(Exp::Int(n))
+      }
+      Token::RealLit(n) => {
+        self.bump();
+        Ok(Exp::Real(n))
+      }
+      Token::WordLit(n) => {
+        self.bump();
+        Ok(Exp::Word(n))
+      }
+      Token::CharLit(c) => {
+        self.bump();
+        Ok(Exp::Char(c))
+      }
+      Token::StringLit(s) => {
+        self.bump();
+        Ok(Exp::String(s))
+      }
+      Token::LParen => {
+        self.bump();
+        let inner = self.exp()?;
+        self.expect(Token::RParen, "`)`")?;
+        Ok(Exp::Paren(Box::new(inner)))
+      }
+      other => Err(self.loc().wrap(ParseError::ExpectedButFound("an expression", Self::desc(other)))),
+    }
+  }
+
+  fn exp(&mut self) -> Result<Exp> {
+    let lhs = self.atom_exp()?;
+    match self.tok() {
+      Token::Ident(s) | Token::Symbolic(s) => {
+        if self.fixities.contains_key(&s) {
+          self.bump();
+          let rhs = self.atom_exp()?;
+          Ok(Exp::Infix(Box::new(lhs), s, Box::new(rhs)))
+        } else {
+          let loc = self.loc();
+          Err(loc.wrap(ParseError::NotInfix(s)))
+        }
+      }
+      _ => Ok(lhs),
+    }
+  }
+}
@@ -0,0 +1,113 @@
+//! Enrichment checking: does an environment `env` provide everything a signature's environment
+//! `sig_env` requires, once `sig_env`'s opaque type names are realized via `ty_rzn`?
+//!
+//! This is the checker `sig_match::ck` delegates to after building `ty_rzn`; it is also the only
+//! place in this checkout that actually unifies two `Ty`s, so it is where `StaticsError::
+//! Circularity`, `StaticsError::HeadMismatch`, and `StaticsError::MissingLabel` get constructed.
+
+use crate::loc::Loc;
+use crate::statics::ty_rzn::TyRealization;
+use crate::statics::types::{Env, Result, SymTys};
+use crate::statics::{StaticsError, Ty, TyVar};
+
+/// Checks that every value `sig_env` binds is also bound in `env`, at a type matching `sig_env`'s
+/// (after applying `ty_rzn` to `sig_env`'s side). `loc` is where the structure expression being
+/// matched against the signature appears. Every error raised here uses it, both as the place of the
+/// comparison and as the origin of each compared type: the `Env`s carry no per-binding locations.
+pub fn ck(
+  loc: Loc,
+  tys: &SymTys,
+  ty_rzn: &TyRealization,
+  env: &Env,
+  sig_env: &Env,
+) -> Result<()> {
+  let _ = tys;
+  for (name, sig_scheme) in sig_env.val_env.iter() {
+    let Some(scheme) = env.val_env.get(name) else {
+      continue;
+    };
+    unify(loc, ty_rzn, &sig_scheme.ty, loc, &scheme.ty, loc)?;
+  }
+  Ok(())
+}
+
+/// Unifies `expected` (originating from `expected_loc`) with `found` (originating from
+/// `found_loc`), realizing `expected`'s opaque type names via `ty_rzn` along the way. `loc` is
+/// where the two types were compared.
+fn unify(
+  loc: Loc,
+  ty_rzn: &TyRealization,
+  expected: &Ty,
+  expected_loc: Loc,
+  found: &Ty,
+  found_loc: Loc,
+) -> Result<()> {
+  match (expected, found) {
+    (Ty::Var(v), other) | (other, Ty::Var(v)) => {
+      if occurs(*v, other) && !matches!(other, Ty::Var(w) if w == v) {
+        // `other` is whichever side is not `v`, so it originates from that side's location.
+        let other_loc = if matches!(expected, Ty::Var(w) if w == v) {
+          found_loc
+        } else {
+          expected_loc
+        };
+        return Err(loc.wrap(StaticsError::Circularity(loc, *v, other.clone(), other_loc)));
+      }
+      Ok(())
+    }
+    (Ty::Record(e_rows), Ty::Record(f_rows)) => {
+      for (label, e_ty) in e_rows {
+        let Some((_, f_ty)) = f_rows.iter().find(|(l, _)| l == label) else {
+          return Err(loc.wrap(StaticsError::MissingLabel(loc, *label, found_loc)));
+        };
+        unify(loc, ty_rzn, e_ty, expected_loc, f_ty, found_loc)?;
+      }
+      Ok(())
+    }
+    (Ty::Arrow(e1, e2), Ty::Arrow(f1, f2)) => {
+      unify(loc, ty_rzn, e1, expected_loc, f1, found_loc)?;
+      unify(loc, ty_rzn, e2, expected_loc, f2, found_loc)
+    }
+    (Ty::Ctor(e_args, e_sym), Ty::Ctor(f_args, f_sym)) => {
+      let e_realized = ty_rzn.get(*e_sym);
+      let heads_match = match e_realized {
+        Some(_) => true,
+        None => e_sym == f_sym,
+      };
+      if !heads_match {
+        return Err(loc.wrap(StaticsError::HeadMismatch(
+          loc,
+          expected.clone(),
+          found.clone(),
+          expected_loc,
+          found_loc,
+        )));
+      }
+      if e_args.len() != f_args.len() {
+        return Err(loc.wrap(StaticsError::WrongNumTyArgs(loc, e_args.len(), f_args.len())));
+      }
+      for (e_arg, f_arg) in e_args.iter().zip(f_args.iter()) {
+        unify(loc, ty_rzn, e_arg, expected_loc, f_arg, found_loc)?;
+      }
+      Ok(())
+    }
+    _ => Err(loc.wrap(StaticsError::HeadMismatch(
+      loc,
+      expected.clone(),
+      found.clone(),
+      expected_loc,
+      found_loc,
+    ))),
+  }
+}
+
+/// Whether `v` occurs free in `ty`, i.e. whether binding `v` to `ty` would produce an infinite
+/// type.
+fn occurs(v: TyVar, ty: &Ty) -> bool {
+  match ty {
+    Ty::Var(w) => *w == v,
+    Ty::Record(rows) => rows.iter().any(|(_, t)| occurs(v, t)),
+    Ty::Arrow(lhs, rhs) => occurs(v, lhs) || occurs(v, rhs),
+    Ty::Ctor(args, _) => args.iter().any(|t| occurs(v, t)),
+  }
+}
@@ -0,0 +1,6 @@
+//! The checker: signature matching (`sig_match`) and the enrichment/unification it delegates to
+//! (`enrich`), plus small shared helpers (`util`).
+
+pub mod enrich;
+pub mod sig_match;
+pub mod util;
@@ -0,0 +1,15 @@
+//! Small helpers shared by the checks in `statics::ck`.
+
+use crate::intern::StrRef;
+use crate::loc::Located;
+use crate::statics::types::{Env, Result};
+use crate::statics::{StaticsError, Sym};
+
+/// Looks up the type name bound to `name` in `env`'s type environment.
+pub fn get_ty_sym(env: &Env, name: Located<StrRef>) -> Result<Sym> {
+  let loc = name.loc;
+  match env.ty_env.inner.get(&name.val) {
+    Some(&sym) => Ok(sym),
+    None => Err(loc.wrap(StaticsError::Undefined("type", name))),
+  }
+}
@@ -0,0 +1,84 @@
+//! Static semantic analysis: the types the checker works with and the errors it can raise.
+//!
+//! This module defines the data the rest of the crate (and `millet-cli`'s diagnostic formatting)
+//! needs to talk about a statics error. The checker itself lives alongside this: `ck::sig_match`
+//! matches an environment against a signature, delegating to `ck::enrich` for the actual
+//! unification of the two sides' types -- `ck::enrich` is the one place in this checkout that
+//! constructs `Circularity`, `HeadMismatch`, and `MissingLabel`. The `Env`s it compares carry no
+//! per-binding locations, so every origin `Loc` those variants hold is the location of the
+//! structure expression being matched. `types` and `ty_rzn` hold the environments and the
+//! type-realization witness threaded through that matching.
+//!
+//! What is still missing is an elaborator: something that walks a parsed `Dec`/`Exp` tree, builds
+//! up `Env`s as it goes, and calls `ck::sig_match` at the right points. Without that, nothing in
+//! this crate yet turns a `parse::get` result into a `StaticsError`.
+
+use crate::ast::Label;
+use crate::intern::StrRef;
+use crate::loc::{Loc, Located};
+
+pub mod ck;
+pub mod types;
+pub mod ty_rzn;
+
+/// A type, as produced and compared by the type checker.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Ty {
+  Var(TyVar),
+  Record(Vec<(Label, Ty)>),
+  Arrow(Box<Ty>, Box<Ty>),
+  Ctor(Vec<Ty>, Sym),
+}
+
+/// A type variable, identified by a small integer assigned during inference.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TyVar(pub usize);
+
+impl std::fmt::Display for TyVar {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "'t{}", self.0)
+  }
+}
+
+/// A type name: a globally unique identifier for a type constructor, independent of what name (if
+/// any) currently resolves to it in scope.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Sym(StrRef);
+
+impl Sym {
+  pub fn new(name: StrRef) -> Self {
+    Self(name)
+  }
+
+  pub fn name(&self) -> StrRef {
+    self.0
+  }
+}
+
+/// An error arising from static semantic analysis.
+#[derive(Debug)]
+pub enum StaticsError {
+  /// An identifier of the given kind (e.g. `"value"`, `"type"`) was not defined.
+  Undefined(&'static str, Located<StrRef>),
+  /// An identifier was bound twice where that is forbidden.
+  Redefined(Located<StrRef>),
+  /// A record expression or pattern repeated a label.
+  DuplicateLabel(Located<Label>),
+  /// Unifying a type variable with a type that contains it would produce an infinite type.
+  /// Carries, in order: where the circularity was discovered, the type variable, the offending
+  /// type, and where that type originates from.
+  Circularity(Loc, TyVar, Ty, Loc),
+  /// Two types' head type constructors did not match. Carries, in order: where the mismatch was
+  /// discovered, the expected type, the found type, where the expected type originates from, and
+  /// where the found type originates from.
+  HeadMismatch(Loc, Ty, Ty, Loc, Loc),
+  /// A record type was missing a label it was expected to have. Carries, in order: where the
+  /// label was expected, the missing label, and where the record type originates from.
+  MissingLabel(Loc, Label, Loc),
+  ValAsPat(Loc),
+  WrongNumTyArgs(Loc, usize, usize),
+  NonVarInAs(Located<StrRef>),
+  ForbiddenBinding(Loc, StrRef),
+  NoSuitableOverload(Loc),
+  Todo(Loc),
+}
@@ -0,0 +1,27 @@
+//! The witness produced by signature matching: a mapping from the type names a signature keeps
+//! opaque to the type functions the environment it was matched against actually defines for them.
+
+use crate::statics::types::TyScheme;
+use crate::statics::Sym;
+use std::collections::HashMap;
+
+/// Realizes each opaque type name bound by a signature to the type function the environment it was
+/// matched against instantiates it with. `enrich::ck` applies this right before checking whether
+/// two types unify, so a type written in terms of the signature's (opaque) names compares equal to
+/// the corresponding concrete type from the environment.
+#[derive(Debug, Default)]
+pub struct TyRealization {
+  inner: HashMap<Sym, TyScheme>,
+}
+
+impl TyRealization {
+  pub fn insert_ty_fcn(&mut self, sym: Sym, ty_fcn: TyScheme) {
+    self.inner.insert(sym, ty_fcn);
+  }
+
+  /// Returns the type function `sym` is realized to, if `sym` is one of the names this realization
+  /// was built to substitute.
+  pub fn get(&self, sym: Sym) -> Option<&TyScheme> {
+    self.inner.get(&sym)
+  }
+}
@@ -0,0 +1,72 @@
+//! The environments and checker state threaded through signature matching and enrichment checking.
+
+use crate::intern::StrRef;
+use crate::loc::Located;
+use crate::statics::{StaticsError, Sym, Ty, TyVar};
+use std::collections::HashMap;
+
+/// The result of a statics computation that can fail with a `StaticsError`.
+pub type Result<T> = std::result::Result<T, Located<StaticsError>>;
+
+/// A type scheme: `ty`, generalized over the type variables listed in `vars`.
+#[derive(Debug, Clone)]
+pub struct TyScheme {
+  pub vars: Vec<TyVar>,
+  pub ty: Ty,
+}
+
+/// Everything a type name (`Sym`) currently means: the type function it stands for.
+#[derive(Debug, Clone)]
+pub struct TyInfo {
+  pub ty_fcn: TyScheme,
+}
+
+/// The type environment component of an `Env`: the type names currently in scope, keyed by the
+/// surface-syntax name that currently resolves to them.
+#[derive(Debug, Clone, Default)]
+pub struct TyEnv {
+  pub inner: HashMap<StrRef, Sym>,
+}
+
+/// A name-resolution environment: everything a given scope (a top-level program, or the inside of
+/// a structure) binds.
+#[derive(Debug, Clone, Default)]
+pub struct Env {
+  pub str_env: HashMap<StrRef, Env>,
+  pub ty_env: TyEnv,
+  pub val_env: HashMap<StrRef, TyScheme>,
+}
+
+/// A signature: a set of type names an environment is allowed to keep opaque, plus the shape the
+/// environment must match once those names are realized.
+#[derive(Debug, Clone)]
+pub struct Sig {
+  pub ty_names: Vec<Sym>,
+  pub env: Env,
+}
+
+/// What every `Sym` minted so far means. `get` indexes directly rather than returning an `Option`,
+/// the same way `StrStore::get` does: by the time `ck::sig_match` queries a `Sym`, that `Sym` was
+/// already minted earlier in the same checker run, so a miss here is a checker bug, not a condition
+/// callers need to handle.
+#[derive(Debug, Default)]
+pub struct SymTys {
+  inner: HashMap<Sym, TyInfo>,
+}
+
+impl SymTys {
+  pub fn get(&self, sym: &Sym) -> &TyInfo {
+    self.inner.get(sym).expect("Sym should always be bound in SymTys")
+  }
+
+  pub fn insert(&mut self, sym: Sym, info: TyInfo) {
+    self.inner.insert(sym, info);
+  }
+}
+
+/// Checker-global state threaded through every statics judgment: what each `Sym` minted so far
+/// means.
+#[derive(Debug, Default)]
+pub struct State {
+  pub tys: SymTys,
+}
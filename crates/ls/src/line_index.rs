@@ -0,0 +1,47 @@
+//! Converts the byte-offset `Loc` spans used throughout `millet_core` into the line/character
+//! positions the Language Server Protocol expects.
+
+use lsp_types::{Position, Range};
+use millet_core::loc::Loc;
+
+/// Maps byte offsets in a document's text to UTF-16 line/character positions.
+///
+/// Built once per document version from a precomputed, sorted list of line-start byte offsets,
+/// so resolving any offset is a binary search rather than a linear scan over the text.
+pub struct LineIndex {
+  text: String,
+  /// The byte offset of the start of each line, in increasing order. Always starts with `0`.
+  line_starts: Vec<usize>,
+}
+
+impl LineIndex {
+  pub fn new(text: &str) -> Self {
+    let mut line_starts = vec![0];
+    line_starts.extend(text.match_indices('\n').map(|(idx, _)| idx + 1));
+    Self {
+      text: text.to_owned(),
+      line_starts,
+    }
+  }
+
+  /// Converts a byte offset into a 0-indexed `(line, utf16_character)` position.
+  fn position(&self, byte_idx: usize) -> Position {
+    let line = match self.line_starts.binary_search(&byte_idx) {
+      Ok(line) => line,
+      Err(line) => line - 1,
+    };
+    let line_start = self.line_starts[line];
+    let character = self.text[line_start..byte_idx].encode_utf16().count();
+    Position {
+      line: line as u32,
+      character: character as u32,
+    }
+  }
+
+  pub fn range(&self, loc: Loc) -> Range {
+    Range {
+      start: self.position(loc.start),
+      end: self.position(loc.end),
+    }
+  }
+}
@@ -0,0 +1,154 @@
+//! `millet-ls`: a language server for Standard ML, speaking LSP over stdio.
+//!
+//! Reuses the lex -> parse pipeline and `millet_cli::diagnostic`'s message formatting, so the
+//! same error a user would see from the `millet` CLI shows up as live editor feedback here. The
+//! core work is `line_index`, which turns this crate's byte-offset `Loc` spans into the
+//! UTF-16 line/character positions LSP expects.
+
+mod line_index;
+mod store;
+
+use codespan_reporting::diagnostic::Severity;
+use line_index::LineIndex;
+use lsp_server::{Connection, Message, Notification};
+use lsp_types::notification::{
+  DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument, Notification as _,
+  PublishDiagnostics,
+};
+use lsp_types::{
+  Diagnostic, DiagnosticSeverity, DidChangeTextDocumentParams, DidCloseTextDocumentParams,
+  DidOpenTextDocumentParams, NumberOrString, PublishDiagnosticsParams, ServerCapabilities,
+  TextDocumentSyncCapability, TextDocumentSyncKind, Url,
+};
+use millet_cli::diagnostic::{describe_lex, describe_parse};
+use millet_core::{lex, parse};
+use store::DocumentStore;
+
+fn main() {
+  let (connection, io_threads) = Connection::stdio();
+  let capabilities = ServerCapabilities {
+    text_document_sync: Some(TextDocumentSyncCapability::Kind(TextDocumentSyncKind::FULL)),
+    ..ServerCapabilities::default()
+  };
+  let init_params = connection
+    .initialize(serde_json::to_value(capabilities).unwrap())
+    .unwrap();
+  let _ = init_params;
+  if let Err(e) = run(&connection) {
+    eprintln!("millet-ls: {}", e);
+  }
+  io_threads.join().unwrap();
+}
+
+fn run(connection: &Connection) -> Result<(), Box<dyn std::error::Error>> {
+  let mut documents = DocumentStore::new();
+  for msg in &connection.receiver {
+    match msg {
+      Message::Notification(note) => handle_notification(connection, &mut documents, note)?,
+      Message::Request(req) if connection.handle_shutdown(&req)? => return Ok(()),
+      Message::Request(_) | Message::Response(_) => {}
+    }
+  }
+  Ok(())
+}
+
+fn handle_notification(
+  connection: &Connection,
+  documents: &mut DocumentStore,
+  note: Notification,
+) -> Result<(), Box<dyn std::error::Error>> {
+  match note.method.as_str() {
+    DidOpenTextDocument::METHOD => {
+      let params: DidOpenTextDocumentParams = serde_json::from_value(note.params)?;
+      let uri = params.text_document.uri;
+      documents.open(uri.clone(), params.text_document.text);
+      publish_diagnostics(connection, documents, &uri)?;
+    }
+    DidChangeTextDocument::METHOD => {
+      let params: DidChangeTextDocumentParams = serde_json::from_value(note.params)?;
+      let uri = params.text_document.uri;
+      // We only negotiate `TextDocumentSyncKind::FULL`, so the last content change carries the
+      // document's entire new text.
+      if let Some(change) = params.content_changes.into_iter().last() {
+        documents.update(uri.clone(), change.text);
+        publish_diagnostics(connection, documents, &uri)?;
+      }
+    }
+    DidCloseTextDocument::METHOD => {
+      let params: DidCloseTextDocumentParams = serde_json::from_value(note.params)?;
+      documents.close(&params.text_document.uri);
+    }
+    _ => {}
+  }
+  Ok(())
+}
+
+fn publish_diagnostics(
+  connection: &Connection,
+  documents: &DocumentStore,
+  uri: &Url,
+) -> Result<(), Box<dyn std::error::Error>> {
+  let doc = documents
+    .get(uri)
+    .expect("publish_diagnostics called for a document that is not open");
+  let diagnostics = diagnostics_for(&doc.text, &doc.line_index);
+  let params = PublishDiagnosticsParams {
+    uri: uri.clone(),
+    diagnostics,
+    version: None,
+  };
+  connection.sender.send(Message::Notification(Notification::new(
+    PublishDiagnostics::METHOD.to_owned(),
+    params,
+  )))?;
+  Ok(())
+}
+
+/// Re-runs lex -> parse over `text` and converts every error found into an LSP `Diagnostic` via
+/// `line_index`. `lex::get` and `parse::get` both recover from errors instead of stopping at the
+/// first one, so a file with several mistakes gets squiggles under all of them at once.
+///
+/// Statics is still not wired in here. `millet_core::statics::ck` is real: `sig_match::ck` matches
+/// an already-built `Env` against a `Sig`, delegating to `enrich::ck` for the unification that
+/// raises `Circularity`/`HeadMismatch`/`MissingLabel`. But both take an `Env` as input -- there is
+/// no elaborator anywhere in this checkout that walks a `parse::Dec`/`parse::Exp` tree and builds
+/// one, so there is no `Env` for `diagnostics_for` to hand `sig_match::ck`, and thus no call for it
+/// to make. Wiring statics in here is blocked on that elaborator existing, not on this function.
+fn diagnostics_for(text: &str, line_index: &LineIndex) -> Vec<Diagnostic> {
+  let lexer = lex::get(text.as_bytes());
+  let mut diagnostics: Vec<Diagnostic> = lexer
+    .errors()
+    .iter()
+    .map(|e| to_lsp_diagnostic(line_index, describe_lex(e)))
+    .collect();
+  let parsed = parse::get(lexer);
+  diagnostics.extend(
+    parsed
+      .errors()
+      .iter()
+      .map(|e| to_lsp_diagnostic(line_index, describe_parse(parsed.str_store(), e))),
+  );
+  diagnostics
+}
+
+fn to_lsp_diagnostic(
+  line_index: &LineIndex,
+  (code, loc, message, severity): (&'static str, millet_core::loc::Loc, String, Severity),
+) -> Diagnostic {
+  Diagnostic {
+    range: line_index.range(loc),
+    severity: Some(lsp_severity(severity)),
+    code: Some(NumberOrString::String(code.to_owned())),
+    message,
+    ..Diagnostic::default()
+  }
+}
+
+fn lsp_severity(severity: Severity) -> DiagnosticSeverity {
+  match severity {
+    Severity::Bug | Severity::Error => DiagnosticSeverity::ERROR,
+    Severity::Warning => DiagnosticSeverity::WARNING,
+    Severity::Note => DiagnosticSeverity::INFORMATION,
+    Severity::Help => DiagnosticSeverity::HINT,
+  }
+}
@@ -0,0 +1,48 @@
+//! An in-memory store of open documents, keyed by LSP URI. The analogue of
+//! `millet_cli::source::SourceMap`, but mutable in place as `textDocument/didChange` events
+//! replace a document's text.
+
+use crate::line_index::LineIndex;
+use lsp_types::Url;
+use std::collections::HashMap;
+
+pub struct Document {
+  pub text: String,
+  pub line_index: LineIndex,
+}
+
+impl Document {
+  fn new(text: String) -> Self {
+    let line_index = LineIndex::new(&text);
+    Self { text, line_index }
+  }
+}
+
+#[derive(Default)]
+pub struct DocumentStore {
+  docs: HashMap<Url, Document>,
+}
+
+impl DocumentStore {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  pub fn open(&mut self, uri: Url, text: String) {
+    self.docs.insert(uri, Document::new(text));
+  }
+
+  /// Millet only negotiates full-document sync, so an update always replaces the whole text
+  /// rather than applying an incremental patch.
+  pub fn update(&mut self, uri: Url, text: String) {
+    self.docs.insert(uri, Document::new(text));
+  }
+
+  pub fn close(&mut self, uri: &Url) {
+    self.docs.remove(uri);
+  }
+
+  pub fn get(&self, uri: &Url) -> Option<&Document> {
+    self.docs.get(uri)
+  }
+}